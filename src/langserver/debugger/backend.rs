@@ -0,0 +1,241 @@
+//! Transport for the debug channel exposed by a running DreamSeeker
+//! instance, analogous to how the Helix client connects its adapter to its
+//! backend over TCP. The wire format is line-delimited JSON: one command
+//! per line out, one reply per line back.
+
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use io;
+use super::dap_types::{ContinuedEvent, Event, EventMessage, ProtocolMessage, StoppedEvent};
+use super::MAIN_THREAD_ID;
+
+pub struct Backend {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Backend {
+    pub fn connect(host: &str, port: u16) -> std::io::Result<Backend> {
+        let stream = TcpStream::connect((host, port))?;
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Backend { stream, reader })
+    }
+
+    // Retry for a little while: the game may not have opened its debug
+    // channel listener yet by the time we try to connect.
+    pub fn connect_with_retry(host: &str, port: u16, attempts: u32, delay: Duration) -> std::io::Result<Backend> {
+        let mut last_err = None;
+        for _ in 0..attempts {
+            match Backend::connect(host, port) {
+                Ok(backend) => return Ok(backend),
+                Err(err) => {
+                    last_err = Some(err);
+                    std::thread::sleep(delay);
+                }
+            }
+        }
+        Err(last_err.expect("attempts must be > 0"))
+    }
+
+    pub fn request(&mut self, command: &BackendCommand) -> Result<BackendReply, Box<dyn Error>> {
+        let mut line = serde_json::to_string(command)?;
+        line.push('\n');
+        self.stream.write_all(line.as_bytes())?;
+
+        let mut reply_line = String::new();
+        self.reader.read_line(&mut reply_line)?;
+        if reply_line.is_empty() {
+            return Err("debug channel closed".into());
+        }
+        Ok(serde_json::from_str(&reply_line)?)
+    }
+}
+
+// The request/reply channel above can't also carry stop/continue
+// notifications: DreamSeeker reports those on its own schedule (a
+// breakpoint hit, a step finishing, a pause taking effect), not in
+// response to a specific command, so they'd otherwise race with whatever
+// reply a concurrent request is waiting to read. Instead, open a second
+// connection purely for these pushed notifications and translate each one
+// directly into the matching DAP event from this background thread, so
+// the adapter's synchronous request loop is never blocked waiting for the
+// game to stop.
+pub fn spawn_notification_listener(host: String, port: u16, seq: Arc<AtomicI64>, handles_stale: Arc<AtomicBool>) {
+    std::thread::Builder::new()
+        .name("debug channel notification listener".to_owned())
+        .spawn(move || {
+            let backend = match Backend::connect_with_retry(&host, port, 20, Duration::from_millis(100)) {
+                Ok(backend) => backend,
+                Err(err) => {
+                    eprintln!("could not open debug channel notification listener at {}:{}: {}", host, port, err);
+                    return;
+                }
+            };
+            let mut reader = backend.reader;
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line) {
+                    Ok(0) => return, // channel closed
+                    Err(err) => {
+                        eprintln!("debug channel notification listener stopped: {}", err);
+                        return;
+                    }
+                    Ok(_) => {}
+                }
+
+                let notification: BackendNotification = match serde_json::from_str(&line) {
+                    Ok(notification) => notification,
+                    Err(_) => continue,
+                };
+
+                match notification {
+                    BackendNotification::Stopped { reason } => {
+                        handles_stale.store(true, Ordering::SeqCst);
+                        emit_event(&seq, StoppedEvent {
+                            reason,
+                            threadId: Some(MAIN_THREAD_ID),
+                            allThreadsStopped: Some(true),
+                        });
+                    }
+                    BackendNotification::Continued => {
+                        emit_event(&seq, ContinuedEvent {
+                            threadId: MAIN_THREAD_ID,
+                            allThreadsContinued: Some(true),
+                        });
+                    }
+                }
+            }
+        })
+        .expect("failed to spawn debug channel notification listener");
+}
+
+// Mirrors `Debugger::issue_event`, but for use from a thread that doesn't
+// own a `Debugger` (seq numbers are shared via `Arc<AtomicI64>` instead).
+fn emit_event<E: Event>(seq: &Arc<AtomicI64>, event: E) {
+    let body = serde_json::to_value(event).expect("event body encode error");
+    let message = EventMessage {
+        protocol_message: ProtocolMessage {
+            seq: seq.fetch_add(1, Ordering::SeqCst) + 1,
+            type_: EventMessage::TYPE.to_owned(),
+        },
+        event: E::EVENT.to_owned(),
+        body: Some(body),
+    };
+    io::write(serde_json::to_string(&message).expect("event encode error"));
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum BackendNotification {
+    Stopped { reason: String },
+    Continued,
+}
+
+// `rename_all` on the enum itself only case-converts the `command`/`kind`
+// tag, not the fields of a struct-like variant, so each variant (and each
+// nested struct below) that has a multi-word field name also carries its
+// own `rename_all` to actually put it on the wire as camelCase, matching
+// every real DAP message in `dap_types.rs`.
+#[derive(Serialize)]
+#[serde(tag = "command", rename_all = "camelCase")]
+pub enum BackendCommand {
+    #[serde(rename_all = "camelCase")]
+    StackTrace { thread_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    Scopes { frame_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    Variables { object_ref: String, start: Option<i64>, count: Option<i64> },
+    #[serde(rename_all = "camelCase")]
+    Continue { thread_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    Next { thread_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    StepIn { thread_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    StepOut { thread_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    Pause { thread_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    Evaluate { frame_id: Option<i64>, expression: String, allow_side_effects: bool },
+    // Replaces whatever source breakpoints DreamSeeker has on file for
+    // `path` with `breakpoints`, in order.
+    SetBreakpoints { path: String, breakpoints: Vec<BackendSourceBreakpoint> },
+    // Replaces the whole set of function breakpoints, in order.
+    SetFunctionBreakpoints { breakpoints: Vec<BackendFunctionBreakpoint> },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendSourceBreakpoint {
+    pub line: i64,
+    pub condition: Option<String>,
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendFunctionBreakpoint {
+    pub name: String,
+    pub condition: Option<String>,
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum BackendReply {
+    StackTrace { frames: Vec<BackendFrame> },
+    Scopes { scopes: Vec<BackendScope> },
+    Variables { variables: Vec<BackendVariable> },
+    // sent in reply to Continue/Next/StepIn/StepOut/Pause to confirm the
+    // command was received; the game reports whether/when it actually
+    // stops again as an asynchronous `BackendNotification` instead, since
+    // that can happen a long time after (or without) any specific request.
+    Ack,
+    #[serde(rename_all = "camelCase")]
+    Evaluate { result: String, object_ref: Option<String>, type_name: Option<String> },
+    // sent in reply to SetBreakpoints/SetFunctionBreakpoints, one status per
+    // breakpoint in the order they were sent
+    Breakpoints { breakpoints: Vec<BackendBreakpointStatus> },
+    Error { message: String },
+}
+
+#[derive(Deserialize)]
+pub struct BackendBreakpointStatus {
+    pub verified: bool,
+    pub line: Option<i64>,
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BackendFrame {
+    pub id: i64,
+    pub name: String,
+    pub path: Option<String>,
+    pub line: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendScope {
+    pub name: String,
+    pub object_ref: Option<String>,
+    pub expensive: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendVariable {
+    pub name: String,
+    pub value: String,
+    pub type_name: Option<String>,
+    pub object_ref: Option<String>,
+}
@@ -0,0 +1,571 @@
+//! Generated-by-hand bindings to the pieces of the Debug Adapter Protocol
+//! that this adapter actually speaks.
+//!
+//! https://microsoft.github.io/debug-adapter-protocol/specification
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub trait Request {
+    type Params: DeserializeOwned;
+    type Result: Serialize;
+    const COMMAND: &'static str;
+}
+
+pub trait Event: Serialize {
+    const EVENT: &'static str;
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProtocolMessage {
+    pub seq: i64,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestMessage {
+    #[serde(flatten)]
+    pub protocol_message: ProtocolMessage,
+    pub command: String,
+    pub arguments: Option<Value>,
+}
+
+impl RequestMessage {
+    pub const TYPE: &'static str = "request";
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ResponseMessage {
+    #[serde(flatten)]
+    pub protocol_message: ProtocolMessage,
+    pub request_seq: i64,
+    pub success: bool,
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl ResponseMessage {
+    pub const TYPE: &'static str = "response";
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventMessage {
+    #[serde(flatten)]
+    pub protocol_message: ProtocolMessage,
+    pub event: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl EventMessage {
+    pub const TYPE: &'static str = "event";
+}
+
+// ----------------------------------------------------------------------------
+// initialize
+
+pub enum Initialize {}
+
+impl Request for Initialize {
+    type Params = InitializeRequestArguments;
+    type Result = Option<Capabilities>;
+    const COMMAND: &'static str = "initialize";
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[allow(non_snake_case)]
+pub struct InitializeRequestArguments {
+    pub clientID: Option<String>,
+    pub clientName: Option<String>,
+    pub adapterID: Option<String>,
+    pub locale: Option<String>,
+    pub linesStartAt1: Option<bool>,
+    pub columnsStartAt1: Option<bool>,
+    pub pathFormat: Option<String>,
+    pub supportsVariableType: Option<bool>,
+    pub supportsVariablePaging: Option<bool>,
+    pub supportsRunInTerminalRequest: Option<bool>,
+    pub supportsMemoryReferences: Option<bool>,
+}
+
+#[derive(Default, Serialize)]
+#[allow(non_snake_case)]
+pub struct Capabilities {
+    pub supportsConfigurationDoneRequest: Option<bool>,
+    pub supportsFunctionBreakpoints: Option<bool>,
+    pub supportsConditionalBreakpoints: Option<bool>,
+    pub supportsHitConditionalBreakpoints: Option<bool>,
+    pub supportsEvaluateForHovers: Option<bool>,
+    pub supportsTerminateRequest: Option<bool>,
+    pub supportTerminateDebuggee: Option<bool>,
+    pub supportsRunInTerminalRequest: Option<bool>,
+}
+
+#[derive(Default, Serialize)]
+pub struct InitializedEvent {}
+
+impl Event for InitializedEvent {
+    const EVENT: &'static str = "initialized";
+}
+
+// ----------------------------------------------------------------------------
+// configurationDone
+
+pub enum ConfigurationDone {}
+
+impl Request for ConfigurationDone {
+    type Params = ConfigurationDoneArguments;
+    type Result = ();
+    const COMMAND: &'static str = "configurationDone";
+}
+
+#[derive(Default, Deserialize)]
+pub struct ConfigurationDoneArguments {}
+
+// ----------------------------------------------------------------------------
+// launch
+
+pub enum Launch {}
+
+impl Request for Launch {
+    type Params = LaunchRequestArguments;
+    type Result = ();
+    const COMMAND: &'static str = "launch";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct LaunchRequestArguments {
+    pub noDebug: Option<bool>,
+}
+
+// ----------------------------------------------------------------------------
+// attach
+
+pub enum Attach {}
+
+impl Request for Attach {
+    type Params = AttachRequestArguments;
+    type Result = ();
+    const COMMAND: &'static str = "attach";
+}
+
+#[derive(Default, Deserialize)]
+pub struct AttachRequestArguments {
+    pub restart: Option<Value>,
+}
+
+// ----------------------------------------------------------------------------
+// disconnect
+
+pub enum Disconnect {}
+
+impl Request for Disconnect {
+    type Params = DisconnectArguments;
+    type Result = ();
+    const COMMAND: &'static str = "disconnect";
+}
+
+#[derive(Default, Deserialize)]
+#[allow(non_snake_case)]
+pub struct DisconnectArguments {
+    pub restart: Option<bool>,
+    pub terminateDebuggee: Option<bool>,
+    pub suspendDebuggee: Option<bool>,
+}
+
+#[derive(Default, Serialize)]
+#[allow(non_snake_case)]
+pub struct ExitedEvent {
+    pub exitCode: i64,
+}
+
+impl Event for ExitedEvent {
+    const EVENT: &'static str = "exited";
+}
+
+#[derive(Default, Serialize)]
+pub struct TerminatedEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<Value>,
+}
+
+impl Event for TerminatedEvent {
+    const EVENT: &'static str = "terminated";
+}
+
+// ----------------------------------------------------------------------------
+// terminate
+//
+// Distinct from `disconnect`: a client that gets `supportsTerminateRequest`
+// uses this to ask the debuggee to exit while still treating the session as
+// alive (e.g. VS Code's "stop" vs. its "disconnect"). We only honor it for a
+// debuggee we actually launched; we were never handed enough control over an
+// attached one to terminate it.
+
+pub enum Terminate {}
+
+impl Request for Terminate {
+    type Params = TerminateArguments;
+    type Result = ();
+    const COMMAND: &'static str = "terminate";
+}
+
+#[derive(Default, Deserialize)]
+pub struct TerminateArguments {
+    pub restart: Option<bool>,
+}
+
+// ----------------------------------------------------------------------------
+// setBreakpoints / setFunctionBreakpoints
+
+pub enum SetBreakpoints {}
+
+impl Request for SetBreakpoints {
+    type Params = SetBreakpointsArguments;
+    type Result = SetBreakpointsResponseBody;
+    const COMMAND: &'static str = "setBreakpoints";
+}
+
+#[derive(Deserialize)]
+pub struct SetBreakpointsArguments {
+    pub source: Source,
+    #[serde(default)]
+    pub breakpoints: Vec<SourceBreakpoint>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Source {
+    pub name: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SourceBreakpoint {
+    pub line: i64,
+    pub column: Option<i64>,
+    pub condition: Option<String>,
+    pub hitCondition: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SetBreakpointsResponseBody {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Breakpoint {
+    pub id: Option<i64>,
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<i64>,
+}
+
+pub enum SetFunctionBreakpoints {}
+
+impl Request for SetFunctionBreakpoints {
+    type Params = SetFunctionBreakpointsArguments;
+    type Result = SetBreakpointsResponseBody;
+    const COMMAND: &'static str = "setFunctionBreakpoints";
+}
+
+#[derive(Deserialize)]
+pub struct SetFunctionBreakpointsArguments {
+    pub breakpoints: Vec<FunctionBreakpoint>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct FunctionBreakpoint {
+    pub name: String,
+    pub condition: Option<String>,
+    pub hitCondition: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BreakpointEvent {
+    pub reason: &'static str,
+    pub breakpoint: Breakpoint,
+}
+
+impl Event for BreakpointEvent {
+    const EVENT: &'static str = "breakpoint";
+}
+
+// ----------------------------------------------------------------------------
+// runInTerminal (reverse request: adapter -> client)
+
+pub enum RunInTerminal {}
+
+impl Request for RunInTerminal {
+    type Params = RunInTerminalRequestArguments;
+    type Result = RunInTerminalResponseBody;
+    const COMMAND: &'static str = "runInTerminal";
+}
+
+#[derive(Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct RunInTerminalRequestArguments {
+    pub kind: Option<String>,
+    pub title: Option<String>,
+    pub cwd: String,
+    pub args: Vec<String>,
+    pub env: Option<Value>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct RunInTerminalResponseBody {
+    pub processId: Option<i64>,
+    pub shellProcessId: Option<i64>,
+}
+
+// ----------------------------------------------------------------------------
+// threads / stackTrace / scopes / variables
+
+pub enum Threads {}
+
+impl Request for Threads {
+    type Params = ();
+    type Result = ThreadsResponseBody;
+    const COMMAND: &'static str = "threads";
+}
+
+#[derive(Serialize)]
+pub struct ThreadsResponseBody {
+    pub threads: Vec<Thread>,
+}
+
+#[derive(Serialize)]
+pub struct Thread {
+    pub id: i64,
+    pub name: String,
+}
+
+pub enum StackTrace {}
+
+impl Request for StackTrace {
+    type Params = StackTraceArguments;
+    type Result = StackTraceResponseBody;
+    const COMMAND: &'static str = "stackTrace";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct StackTraceArguments {
+    pub threadId: i64,
+    pub startFrame: Option<i64>,
+    pub levels: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+pub struct StackTraceResponseBody {
+    pub stackFrames: Vec<StackFrame>,
+    pub totalFrames: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct StackFrame {
+    pub id: i64,
+    pub name: String,
+    pub source: Option<Source>,
+    pub line: i64,
+    pub column: i64,
+}
+
+pub enum Scopes {}
+
+impl Request for Scopes {
+    type Params = ScopesArguments;
+    type Result = ScopesResponseBody;
+    const COMMAND: &'static str = "scopes";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct ScopesArguments {
+    pub frameId: i64,
+}
+
+#[derive(Serialize)]
+pub struct ScopesResponseBody {
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+pub struct Scope {
+    pub name: String,
+    pub variablesReference: i64,
+    pub expensive: bool,
+}
+
+pub enum Variables {}
+
+impl Request for Variables {
+    type Params = VariablesArguments;
+    type Result = VariablesResponseBody;
+    const COMMAND: &'static str = "variables";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct VariablesArguments {
+    pub variablesReference: i64,
+    pub start: Option<i64>,
+    pub count: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct VariablesResponseBody {
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    pub variablesReference: i64,
+}
+
+// ----------------------------------------------------------------------------
+// continue / next / stepIn / stepOut / pause
+
+pub enum Continue {}
+
+impl Request for Continue {
+    type Params = ContinueArguments;
+    type Result = ContinueResponseBody;
+    const COMMAND: &'static str = "continue";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct ContinueArguments {
+    pub threadId: i64,
+}
+
+#[derive(Default, Serialize)]
+#[allow(non_snake_case)]
+pub struct ContinueResponseBody {
+    pub allThreadsContinued: Option<bool>,
+}
+
+pub enum Next {}
+
+impl Request for Next {
+    type Params = NextArguments;
+    type Result = ();
+    const COMMAND: &'static str = "next";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct NextArguments {
+    pub threadId: i64,
+}
+
+pub enum StepIn {}
+
+impl Request for StepIn {
+    type Params = StepInArguments;
+    type Result = ();
+    const COMMAND: &'static str = "stepIn";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct StepInArguments {
+    pub threadId: i64,
+}
+
+pub enum StepOut {}
+
+impl Request for StepOut {
+    type Params = StepOutArguments;
+    type Result = ();
+    const COMMAND: &'static str = "stepOut";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct StepOutArguments {
+    pub threadId: i64,
+}
+
+pub enum Pause {}
+
+impl Request for Pause {
+    type Params = PauseArguments;
+    type Result = ();
+    const COMMAND: &'static str = "pause";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct PauseArguments {
+    pub threadId: i64,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+pub struct StoppedEvent {
+    pub reason: String,
+    pub threadId: Option<i64>,
+    pub allThreadsStopped: Option<bool>,
+}
+
+impl Event for StoppedEvent {
+    const EVENT: &'static str = "stopped";
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+pub struct ContinuedEvent {
+    pub threadId: i64,
+    pub allThreadsContinued: Option<bool>,
+}
+
+impl Event for ContinuedEvent {
+    const EVENT: &'static str = "continued";
+}
+
+// ----------------------------------------------------------------------------
+// evaluate
+
+pub enum Evaluate {}
+
+impl Request for Evaluate {
+    type Params = EvaluateArguments;
+    type Result = EvaluateResponseBody;
+    const COMMAND: &'static str = "evaluate";
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+pub struct EvaluateArguments {
+    pub expression: String,
+    pub frameId: Option<i64>,
+    pub context: Option<String>,
+}
+
+#[derive(Default, Serialize)]
+#[allow(non_snake_case)]
+pub struct EvaluateResponseBody {
+    pub result: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    pub variablesReference: i64,
+}
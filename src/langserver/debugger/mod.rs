@@ -2,14 +2,52 @@
 //!
 //! * https://microsoft.github.io/debug-adapter-protocol/
 
+mod backend;
 mod dap_types;
 
+use std::collections::HashMap;
 use std::error::Error;
 use std::process::{Command, Stdio, Child};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use io;
+use self::backend::{Backend, BackendCommand, BackendFunctionBreakpoint, BackendReply, BackendSourceBreakpoint};
 use self::dap_types::*;
 
+// BYOND's scheduler is effectively single-threaded from the debugger's
+// point of view, so every stop/continue event is tied to this one id.
+pub(crate) const MAIN_THREAD_ID: i64 = 1;
+
+// TODO: make configurable once DreamSeeker grows a flag to pick the port.
+const DEBUG_CHANNEL_PORT: u16 = 2727;
+
+macro_rules! handle_request {
+    ($( on $ty:ident (&mut $self_:ident, $params:ident) $body:block )*) => {
+        impl Debugger {
+            fn handle_request(&mut self, request: RequestMessage) -> Result<serde_json::Value, Box<dyn Error>> {
+                match request.command.as_str() {
+                    $(
+                        <$ty as Request>::COMMAND => {
+                            let $self_ = self;
+                            let $params: <$ty as Request>::Params = match request.arguments {
+                                Some(args) => serde_json::from_value(args)?,
+                                None => serde_json::from_value(serde_json::Value::Null)?,
+                            };
+                            let result: <$ty as Request>::Result = (|| -> Result<_, Box<dyn Error>> {
+                                Ok($body)
+                            })()?;
+                            Ok(serde_json::to_value(result)?)
+                        }
+                    )*
+                    other => Err(format!("unknown request {:?}", other).into()),
+                }
+            }
+        }
+    }
+}
+
 pub fn debugger_main<I: Iterator<Item=String>>(mut args: I) {
     eprintln!("acting as debug adapter");
     let mut dreamseeker_exe = None;
@@ -29,23 +67,70 @@ pub fn debugger_main<I: Iterator<Item=String>>(mut args: I) {
     io::run_forever(|message| debugger.handle_input(message));
 }
 
+// How the current debuggee came to be under our control, which determines
+// e.g. whether `Disconnect` is allowed to kill it.
+enum SessionOrigin {
+    Launched(Child),
+    Attached { pid: Option<u32> },
+}
+
 struct Debugger {
     dreamseeker_exe: String,
-    child: Option<Child>,
-    // TODO: separate field from `child` for attached debugger.
+    origin: Option<SessionOrigin>,
 
-    seq: i64,
+    // Shared so the debug channel's notification listener thread (see
+    // `connect_backend`) can stamp seq numbers on the stop/continue events
+    // it emits without needing access to the rest of `Debugger`.
+    seq: Arc<AtomicI64>,
     client_caps: ClientCaps,
+
+    // source path -> (breakpoint, last-known verified state)
+    source_breakpoints: HashMap<String, Vec<(SourceBreakpoint, bool)>>,
+    function_breakpoints: Vec<(FunctionBreakpoint, bool)>,
+
+    // reverse requests (adapter -> client) awaiting a response, keyed by seq
+    pending_requests: HashMap<i64, &'static str>,
+
+    // buffered `launch` arguments, applied once `configurationDone` arrives
+    // so that breakpoints sent in between are in place before we spawn.
+    pending_launch: Option<PendingLaunch>,
+
+    // connection to the running game's debug channel, once it exists
+    backend: Option<Backend>,
+
+    // variablesReference -> backend object reference, cleared on every stop
+    variable_handles: HashMap<i64, String>,
+    next_variable_handle: i64,
+    // Set by the notification listener thread when it reports a stop;
+    // consumed (and the table actually cleared) the next time the main
+    // thread handles a request that reads `variable_handles`.
+    handles_stale: Arc<AtomicBool>,
+}
+
+struct PendingLaunch {
+    dmb: String,
+    run_in_terminal: bool,
 }
 
 impl Debugger {
     fn new(dreamseeker_exe: String) -> Self {
         Debugger {
             dreamseeker_exe,
-            child: None,
+            origin: None,
 
-            seq: 0,
+            seq: Arc::new(AtomicI64::new(0)),
             client_caps: Default::default(),
+
+            source_breakpoints: HashMap::new(),
+            function_breakpoints: Vec::new(),
+
+            pending_requests: HashMap::new(),
+            pending_launch: None,
+
+            backend: None,
+            variable_handles: HashMap::new(),
+            next_variable_handle: 0,
+            handles_stale: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -77,13 +162,49 @@ impl Debugger {
                         Err(_) => None,
                     }
                 };
-                io::write(serde_json::to_string(&response).expect("response encode error"))
+                io::write(serde_json::to_string(&response).expect("response encode error"));
+
+                // DAP startup sequence: tell the client we're ready for it to
+                // send breakpoints before anything actually starts running.
+                if command == Initialize::COMMAND {
+                    self.issue_event(InitializedEvent::default());
+                }
+            }
+            ResponseMessage::TYPE => {
+                let response = serde_json::from_str::<ResponseMessage>(message)?;
+                if let Some(command) = self.pending_requests.remove(&response.request_seq) {
+                    if !response.success {
+                        eprintln!("reverse request {:?} failed: {:?}", command, response.message);
+                    }
+                } else {
+                    eprintln!("response to unknown reverse request seq {}", response.request_seq);
+                }
             }
             other => return Err(format!("unknown `type` field {:?}", other).into())
         }
         Ok(())
     }
 
+    // Issue an adapter -> client reverse request and remember its seq so the
+    // eventual `ResponseMessage` can be matched back up in `handle_input_inner`.
+    fn issue_request<R: Request>(&mut self, params: R::Params) -> i64
+    where
+        R::Params: serde::Serialize,
+    {
+        let seq = self.next_seq();
+        let message = RequestMessage {
+            protocol_message: ProtocolMessage {
+                seq,
+                type_: RequestMessage::TYPE.to_owned(),
+            },
+            command: R::COMMAND.to_owned(),
+            arguments: Some(serde_json::to_value(params).expect("request body encode error")),
+        };
+        self.pending_requests.insert(seq, R::COMMAND);
+        io::write(serde_json::to_string(&message).expect("request encode error"));
+        seq
+    }
+
     fn issue_event<E: Event>(&mut self, event: E) {
         let body = serde_json::to_value(event).expect("event body encode error");
         let message = EventMessage {
@@ -98,8 +219,196 @@ impl Debugger {
     }
 
     fn next_seq(&mut self) -> i64 {
-        self.seq = self.seq.wrapping_add(1);
-        self.seq
+        self.seq.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    // Fallback used before a backend connection exists: just check the
+    // line is in range of the `.dm` file, without telling DreamSeeker
+    // anything. Real verification happens in `resolve_source_breakpoints`
+    // once we can actually ask the game.
+    fn check_source_line(source: &Source, bp: &SourceBreakpoint) -> Breakpoint {
+        let verified = match &source.path {
+            Some(path) => std::fs::read_to_string(path)
+                .map(|contents| bp.line >= 1 && bp.line as usize <= contents.lines().count())
+                .unwrap_or(false),
+            None => false,
+        };
+        Breakpoint {
+            id: None,
+            verified,
+            message: if verified { None } else { Some("could not resolve source location".to_owned()) },
+            source: Some(source.clone()),
+            line: Some(bp.line),
+        }
+    }
+
+    // Push `breakpoints` for `path` to the backend so DreamSeeker actually
+    // stops there, falling back to a local line check if there's no backend
+    // to ask yet (e.g. before the game has been launched).
+    fn resolve_source_breakpoints(&mut self, path: &str, breakpoints: &[SourceBreakpoint]) -> Vec<Breakpoint> {
+        let source = Source { name: None, path: Some(path.to_owned()) };
+
+        let from_backend = self.backend.as_mut().and_then(|backend| {
+            let backend_breakpoints = breakpoints.iter().map(|bp| BackendSourceBreakpoint {
+                line: bp.line,
+                condition: bp.condition.clone(),
+                hit_condition: bp.hitCondition.clone(),
+            }).collect();
+
+            match backend.request(&BackendCommand::SetBreakpoints { path: path.to_owned(), breakpoints: backend_breakpoints }) {
+                Ok(BackendReply::Breakpoints { breakpoints: statuses }) => Some(statuses),
+                _ => None,
+            }
+        });
+
+        match from_backend {
+            Some(statuses) => statuses.into_iter().map(|status| Breakpoint {
+                id: None,
+                verified: status.verified,
+                message: status.message,
+                source: Some(source.clone()),
+                line: status.line,
+            }).collect(),
+            None => breakpoints.iter().map(|bp| Debugger::check_source_line(&source, bp)).collect(),
+        }
+    }
+
+    // Function breakpoints can't be verified until the backend reports
+    // whether the named proc actually exists, so they start out pending
+    // whenever there's no connection to ask.
+    fn resolve_function_breakpoints(&mut self, breakpoints: &[FunctionBreakpoint]) -> Vec<Breakpoint> {
+        let from_backend = self.backend.as_mut().and_then(|backend| {
+            let backend_breakpoints = breakpoints.iter().map(|bp| BackendFunctionBreakpoint {
+                name: bp.name.clone(),
+                condition: bp.condition.clone(),
+                hit_condition: bp.hitCondition.clone(),
+            }).collect();
+
+            match backend.request(&BackendCommand::SetFunctionBreakpoints { breakpoints: backend_breakpoints }) {
+                Ok(BackendReply::Breakpoints { breakpoints: statuses }) => Some(statuses),
+                _ => None,
+            }
+        });
+
+        match from_backend {
+            Some(statuses) => statuses.into_iter().map(|status| Breakpoint {
+                id: None,
+                verified: status.verified,
+                message: status.message,
+                source: None,
+                line: status.line,
+            }).collect(),
+            None => breakpoints.iter().map(Debugger::pending_function_breakpoint).collect(),
+        }
+    }
+
+    fn pending_function_breakpoint(bp: &FunctionBreakpoint) -> Breakpoint {
+        Breakpoint {
+            id: None,
+            verified: false,
+            message: Some(format!("proc {:?} not yet resolved", bp.name)),
+            source: None,
+            line: None,
+        }
+    }
+
+    // Re-resolve all stored breakpoints against the backend, e.g. after a
+    // relaunch, emitting a `BreakpointEvent` for any that have newly become
+    // verified.
+    fn reapply_breakpoints(&mut self) {
+        let mut newly_verified = Vec::new();
+
+        let paths: Vec<String> = self.source_breakpoints.keys().cloned().collect();
+        for path in paths {
+            let breakpoints: Vec<SourceBreakpoint> = self.source_breakpoints[&path].iter()
+                .map(|(bp, _)| bp.clone())
+                .collect();
+            let results = self.resolve_source_breakpoints(&path, &breakpoints);
+
+            let stored = self.source_breakpoints.get_mut(&path).expect("path was just read from this map");
+            for ((_, verified), resolved) in stored.iter_mut().zip(results.iter()) {
+                if resolved.verified && !*verified {
+                    newly_verified.push(resolved.clone());
+                }
+                *verified = resolved.verified;
+            }
+        }
+
+        if !self.function_breakpoints.is_empty() {
+            let breakpoints: Vec<FunctionBreakpoint> = self.function_breakpoints.iter()
+                .map(|(bp, _)| bp.clone())
+                .collect();
+            let results = self.resolve_function_breakpoints(&breakpoints);
+
+            for ((_, verified), resolved) in self.function_breakpoints.iter_mut().zip(results.iter()) {
+                if resolved.verified && !*verified {
+                    newly_verified.push(resolved.clone());
+                }
+                *verified = resolved.verified;
+            }
+        }
+
+        for event in newly_verified {
+            self.issue_event(BreakpointEvent { reason: "changed", breakpoint: event });
+        }
+    }
+
+    // Connect to the debug channel of a DreamSeeker we just started (or
+    // attached to), retrying briefly since it may not be listening yet. Also
+    // spawns a background listener for the stop/continue notifications the
+    // game reports on its own schedule (see `backend::spawn_notification_listener`).
+    fn connect_backend(&mut self, host: &str, port: u16) {
+        match Backend::connect_with_retry(host, port, 20, Duration::from_millis(100)) {
+            Ok(connected) => {
+                self.backend = Some(connected);
+                backend::spawn_notification_listener(host.to_owned(), port, self.seq.clone(), self.handles_stale.clone());
+            }
+            Err(err) => eprintln!("could not connect to debug channel at {}:{}: {}", host, port, err),
+        }
+    }
+
+    // Send an execution-control command (continue/next/stepIn/stepOut/pause)
+    // and wait only for the backend's acknowledgement that it was received,
+    // not for the game to actually stop again. The eventual `stopped` (or
+    // `continued`) event is reported asynchronously by the notification
+    // listener spawned in `connect_backend`, so that e.g. a `pause` request
+    // can still be serviced while the game is running.
+    fn send_execution_command(&mut self, command: BackendCommand) -> Result<(), Box<dyn Error>> {
+        let reply = {
+            let backend = self.backend.as_mut().ok_or("not connected to a running game")?;
+            backend.request(&command)?
+        };
+        match reply {
+            BackendReply::Ack => Ok(()),
+            BackendReply::Error { message } => Err(message.into()),
+            _ => Err("unexpected backend reply to execution control command".into()),
+        }
+    }
+
+    fn alloc_variable_handle(&mut self, object_ref: String) -> i64 {
+        self.next_variable_handle += 1;
+        let handle = self.next_variable_handle;
+        self.variable_handles.insert(handle, object_ref);
+        handle
+    }
+
+    // Called (via `sync_variable_handles`) after the debuggee stops:
+    // `variablesReference` handles are only valid for the stack that
+    // produced them.
+    fn clear_variable_handles(&mut self) {
+        self.variable_handles.clear();
+        self.next_variable_handle = 0;
+    }
+
+    // The notification listener thread marks `handles_stale` rather than
+    // clearing `variable_handles` itself, since the table is only ever
+    // otherwise touched from this (the main) thread. Call this before
+    // reading or allocating handles so a stop that happened in the
+    // background is picked up before it's acted on.
+    fn sync_variable_handles(&mut self) {
+        if self.handles_stale.swap(false, Ordering::SeqCst) {
+            self.clear_variable_handles();
+        }
     }
 }
 
@@ -119,6 +428,12 @@ handle_request! {
         // Tell the client our caps
         Some(Capabilities {
             supportTerminateDebuggee: Some(true),
+            supportsTerminateRequest: Some(true),
+            supportsConfigurationDoneRequest: Some(true),
+            supportsFunctionBreakpoints: Some(true),
+            supportsConditionalBreakpoints: Some(true),
+            supportsHitConditionalBreakpoints: Some(true),
+            supportsEvaluateForHovers: Some(true),
             .. Default::default()
         })
     }
@@ -126,42 +441,321 @@ handle_request! {
     on LaunchVsc(&mut self, params) {
         let _debug = !params.base.noDebug.unwrap_or(false);
 
-        let child = Command::new(&self.dreamseeker_exe)
-            .arg(&params.dmb)
-            .arg("-trusted")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?;
-        self.child = Some(child);
+        // Defer the actual spawn until `configurationDone`, so breakpoints
+        // sent in between are guaranteed to be in place beforehand.
+        self.pending_launch = Some(PendingLaunch {
+            dmb: params.dmb,
+            run_in_terminal: self.client_caps.run_in_terminal,
+        });
     }
 
-    on Disconnect(&mut self, params) {
-        // TODO: `false` if `attach` was used instead of `launch`.
-        let default_terminate = true;
-        let terminate = params.terminateDebuggee.unwrap_or(default_terminate);
+    on ConfigurationDone(&mut self, _params) {
+        if let Some(pending) = self.pending_launch.take() {
+            if pending.run_in_terminal {
+                // Let the game's stdout/stderr show up in the editor's
+                // integrated terminal instead of being thrown away.
+                self.issue_request::<RunInTerminal>(RunInTerminalRequestArguments {
+                    kind: Some("integrated".to_owned()),
+                    title: Some("DreamSeeker".to_owned()),
+                    cwd: ".".to_owned(),
+                    args: vec![self.dreamseeker_exe.clone(), pending.dmb, "-trusted".to_owned()],
+                    env: None,
+                });
+                // The client's terminal owns the process, not us.
+                self.origin = Some(SessionOrigin::Attached { pid: None });
+            } else {
+                let child = Command::new(&self.dreamseeker_exe)
+                    .arg(&pending.dmb)
+                    .arg("-trusted")
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()?;
+                self.origin = Some(SessionOrigin::Launched(child));
+            }
+            self.connect_backend("127.0.0.1", DEBUG_CHANNEL_PORT);
+            self.reapply_breakpoints();
+        }
+    }
+
+    on AttachVsc(&mut self, params) {
+        // `processId` alone can't locate the debug channel: it's a TCP
+        // socket, not something attachable by pid. We still accept
+        // `processId` alongside `host`/`port` to validate against (and record
+        // it in `SessionOrigin::Attached`), but we can't guess a connection
+        // target from a pid the way a native debugger could attach by pid.
+        let host = params.host.clone().ok_or("attach requires `host` and `port`; `processId` alone isn't enough to locate the debug channel")?;
+        let port = params.port.ok_or("attach requires `host` and `port`; `processId` alone isn't enough to locate the debug channel")?;
+
+        if let Some(pid) = params.processId {
+            if !process_exists(pid as u32) {
+                Err(format!("no process with id {} is running", pid))?;
+            }
+        }
+
+        self.origin = Some(SessionOrigin::Attached {
+            pid: params.processId.map(|pid| pid as u32),
+        });
+        self.connect_backend(&host, port);
+        self.reapply_breakpoints();
+    }
+
+    on SetBreakpoints(&mut self, params) {
+        let results = match params.source.path.clone() {
+            Some(path) => {
+                let results = self.resolve_source_breakpoints(&path, &params.breakpoints);
+                let stored = params.breakpoints.into_iter()
+                    .zip(results.iter().map(|bp| bp.verified))
+                    .collect();
+                self.source_breakpoints.insert(path, stored);
+                results
+            }
+            None => params.breakpoints.iter()
+                .map(|bp| Debugger::check_source_line(&params.source, bp))
+                .collect(),
+        };
+
+        SetBreakpointsResponseBody { breakpoints: results }
+    }
+
+    on SetFunctionBreakpoints(&mut self, params) {
+        let results = self.resolve_function_breakpoints(&params.breakpoints);
+
+        self.function_breakpoints = params.breakpoints.into_iter()
+            .zip(results.iter().map(|bp| bp.verified))
+            .collect();
+
+        SetBreakpointsResponseBody { breakpoints: results }
+    }
+
+    on Threads(&mut self, _params) {
+        ThreadsResponseBody {
+            threads: vec![Thread { id: MAIN_THREAD_ID, name: "DreamSeeker".to_owned() }],
+        }
+    }
+
+    on StackTrace(&mut self, params) {
+        let reply = {
+            let backend = self.backend.as_mut().ok_or("not connected to a running game")?;
+            backend.request(&BackendCommand::StackTrace { thread_id: params.threadId })?
+        };
+        let frames = match reply {
+            BackendReply::StackTrace { frames } => frames,
+            BackendReply::Error { message } => Err(message)?,
+            _ => Err("unexpected backend reply to stackTrace")?,
+        };
+
+        StackTraceResponseBody {
+            totalFrames: Some(frames.len() as i64),
+            stackFrames: frames.into_iter().map(|frame| StackFrame {
+                id: frame.id,
+                name: frame.name,
+                source: frame.path.map(|path| Source { name: None, path: Some(path) }),
+                line: frame.line,
+                column: 1,
+            }).collect(),
+        }
+    }
+
+    on Scopes(&mut self, params) {
+        self.sync_variable_handles();
 
-        if let Some(mut child) = self.child.take() {
-            if terminate {
+        let reply = {
+            let backend = self.backend.as_mut().ok_or("not connected to a running game")?;
+            backend.request(&BackendCommand::Scopes { frame_id: params.frameId })?
+        };
+        let scopes = match reply {
+            BackendReply::Scopes { scopes } => scopes,
+            BackendReply::Error { message } => Err(message)?,
+            _ => Err("unexpected backend reply to scopes")?,
+        };
+
+        let mut result = Vec::with_capacity(scopes.len());
+        for scope in scopes {
+            let variables_reference = match scope.object_ref {
+                Some(object_ref) => self.alloc_variable_handle(object_ref),
+                None => 0,
+            };
+            result.push(Scope {
+                name: scope.name,
+                variablesReference: variables_reference,
+                expensive: scope.expensive,
+            });
+        }
+
+        ScopesResponseBody { scopes: result }
+    }
+
+    on Variables(&mut self, params) {
+        self.sync_variable_handles();
+
+        let object_ref = self.variable_handles.get(&params.variablesReference)
+            .cloned()
+            .ok_or("unknown variablesReference")?;
+
+        let (start, count) = if self.client_caps.variable_paging {
+            (params.start, params.count)
+        } else {
+            (None, None)
+        };
+
+        let reply = {
+            let backend = self.backend.as_mut().ok_or("not connected to a running game")?;
+            backend.request(&BackendCommand::Variables { object_ref, start, count })?
+        };
+        let variables = match reply {
+            BackendReply::Variables { variables } => variables,
+            BackendReply::Error { message } => Err(message)?,
+            _ => Err("unexpected backend reply to variables")?,
+        };
+
+        let mut result = Vec::with_capacity(variables.len());
+        for variable in variables {
+            let variables_reference = match variable.object_ref {
+                Some(object_ref) => self.alloc_variable_handle(object_ref),
+                None => 0,
+            };
+            result.push(Variable {
+                name: variable.name,
+                value: variable.value,
+                type_: if self.client_caps.variable_type { variable.type_name } else { None },
+                variablesReference: variables_reference,
+            });
+        }
+
+        VariablesResponseBody { variables: result }
+    }
+
+    on Continue(&mut self, params) {
+        self.send_execution_command(BackendCommand::Continue { thread_id: params.threadId })?;
+        self.issue_event(ContinuedEvent { threadId: params.threadId, allThreadsContinued: Some(true) });
+        // The eventual `stopped` event (breakpoint, step, pause, ...) is
+        // reported by the notification listener once the game actually
+        // halts again, not here.
+        ContinueResponseBody { allThreadsContinued: Some(true) }
+    }
+
+    on Next(&mut self, params) {
+        self.send_execution_command(BackendCommand::Next { thread_id: params.threadId })?;
+    }
+
+    on StepIn(&mut self, params) {
+        self.send_execution_command(BackendCommand::StepIn { thread_id: params.threadId })?;
+    }
+
+    on StepOut(&mut self, params) {
+        self.send_execution_command(BackendCommand::StepOut { thread_id: params.threadId })?;
+    }
+
+    on Pause(&mut self, params) {
+        self.send_execution_command(BackendCommand::Pause { thread_id: params.threadId })?;
+    }
+
+    on Evaluate(&mut self, params) {
+        self.sync_variable_handles();
+
+        let context = params.context.as_deref().unwrap_or("hover").to_owned();
+        let allow_side_effects = context == "repl";
+
+        let reply = {
+            let backend = self.backend.as_mut().ok_or("not connected to a running game")?;
+            backend.request(&BackendCommand::Evaluate {
+                frame_id: params.frameId,
+                expression: params.expression,
+                allow_side_effects,
+            })?
+        };
+
+        match reply {
+            BackendReply::Evaluate { result, object_ref, type_name } => {
+                let variables_reference = match object_ref {
+                    Some(object_ref) => self.alloc_variable_handle(object_ref),
+                    None => 0,
+                };
+                EvaluateResponseBody {
+                    result,
+                    type_: if self.client_caps.variable_type { type_name } else { None },
+                    variablesReference: variables_reference,
+                }
+            }
+            // Hovers over unrelated text shouldn't pop up an error; degrade
+            // to "no value" instead of failing the request. Watches/REPL
+            // still surface the error so the user knows the expression failed.
+            BackendReply::Error { .. } if context == "hover" => {
+                EvaluateResponseBody { result: String::new(), type_: None, variablesReference: 0 }
+            }
+            BackendReply::Error { message } => Err(message)?,
+            _ => Err("unexpected backend reply to evaluate")?,
+        }
+    }
+
+    on Terminate(&mut self, _params) {
+        match self.origin.take() {
+            Some(SessionOrigin::Launched(mut child)) => {
                 child.kill()?;
                 let status = child.wait()?;
                 let code = status.code().unwrap_or(-1);
-                self.issue_event(ExitedEvent {
-                    exitCode: code as i64,
-                });
-            } else {
-                // On some OSes, a wait() is necessary to free resources.
-                std::thread::Builder::new()
-                    .name("detached debuggee wait() thread".to_owned())
-                    .spawn(move || {
-                        let _ = child.wait();
-                    })?;
+                self.issue_event(ExitedEvent { exitCode: code as i64 });
+            }
+            // We don't own this process, so we have no way to ask it to
+            // exit; put the origin back and report that plainly instead of
+            // pretending `disconnect` semantics apply here too.
+            Some(origin @ SessionOrigin::Attached { .. }) => {
+                self.origin = Some(origin);
+                Err("cannot terminate a debuggee we attached to rather than launched")?;
+            }
+            None => {}
+        }
+    }
+
+    on Disconnect(&mut self, params) {
+        // Never kill a process we didn't start.
+        let default_terminate = match &self.origin {
+            Some(SessionOrigin::Attached { .. }) => false,
+            Some(SessionOrigin::Launched(_)) | None => true,
+        };
+        let terminate = params.terminateDebuggee.unwrap_or(default_terminate);
+
+        match self.origin.take() {
+            Some(SessionOrigin::Launched(mut child)) => {
+                if terminate {
+                    child.kill()?;
+                    let status = child.wait()?;
+                    let code = status.code().unwrap_or(-1);
+                    self.issue_event(ExitedEvent {
+                        exitCode: code as i64,
+                    });
+                } else {
+                    // On some OSes, a wait() is necessary to free resources.
+                    std::thread::Builder::new()
+                        .name("detached debuggee wait() thread".to_owned())
+                        .spawn(move || {
+                            let _ = child.wait();
+                        })?;
+                    self.issue_event(TerminatedEvent::default());
+                }
+            }
+            Some(SessionOrigin::Attached { .. }) => {
                 self.issue_event(TerminatedEvent::default());
             }
+            None => {}
         }
     }
 }
 
+// Best-effort validation for an attach request's `processId`: we have no
+// portable way to check this without an extra dependency, so only bother
+// on the platforms where it's free via procfs.
+#[cfg(target_os = "linux")]
+fn process_exists(pid: u32) -> bool {
+    std::path::Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_exists(_pid: u32) -> bool {
+    true
+}
+
 #[derive(Default, Debug)]
 struct ClientCaps {
     lines_start_at_1: bool,
@@ -206,4 +800,132 @@ pub struct LaunchRequestArgumentsVsc {
     dmb: String,
 
     // other keys: __sessionId, name, preLaunchTask, request, type
+}
+
+enum AttachVsc {}
+
+impl Request for AttachVsc {
+    type Params = AttachRequestArgumentsVsc;
+    type Result = ();
+    const COMMAND: &'static str = Attach::COMMAND;
+}
+
+#[derive(Deserialize)]
+pub struct AttachRequestArgumentsVsc {
+    #[serde(flatten)]
+    base: AttachRequestArguments,
+
+    // connect to an already-running DreamSeeker instead of spawning one
+    host: Option<String>,
+    port: Option<u16>,
+    processId: Option<i64>,
+
+    // other keys: __sessionId, name, request, type
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("spacemandmm-debugger-test-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).expect("failed to write temp file");
+        path.to_str().expect("temp path must be utf8").to_owned()
+    }
+
+    fn no_op_source_breakpoint(line: i64) -> SourceBreakpoint {
+        SourceBreakpoint { line, column: None, condition: None, hitCondition: None }
+    }
+
+    #[test]
+    fn client_caps_parse_defaults() {
+        let caps = ClientCaps::parse(&InitializeRequestArguments::default());
+        assert!(caps.lines_start_at_1);
+        assert!(caps.columns_start_at_1);
+        assert!(!caps.variable_type);
+        assert!(!caps.variable_paging);
+        assert!(!caps.run_in_terminal);
+        assert!(!caps.memory_references);
+    }
+
+    #[test]
+    fn check_source_line_verifies_in_range_lines() {
+        let path = write_temp_file("check_source_line_ok", "line one\nline two\nline three\n");
+        let source = Source { name: None, path: Some(path.clone()) };
+
+        let resolved = Debugger::check_source_line(&source, &no_op_source_breakpoint(2));
+
+        assert!(resolved.verified);
+        assert_eq!(resolved.line, Some(2));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn check_source_line_rejects_out_of_range_lines() {
+        let path = write_temp_file("check_source_line_oob", "only one line\n");
+        let source = Source { name: None, path: Some(path.clone()) };
+
+        let resolved = Debugger::check_source_line(&source, &no_op_source_breakpoint(99));
+
+        assert!(!resolved.verified);
+        assert!(resolved.message.is_some());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn resolve_source_breakpoints_falls_back_without_backend() {
+        let path = write_temp_file("resolve_fallback", "a\nb\nc\n");
+        let mut debugger = Debugger::new("dreamseeker".to_owned());
+
+        let results = debugger.resolve_source_breakpoints(&path, &[
+            no_op_source_breakpoint(2),
+            no_op_source_breakpoint(50),
+        ]);
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].verified);
+        assert!(!results[1].verified);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn reapply_breakpoints_detects_newly_verified() {
+        let path = write_temp_file("reapply", "only one line\n");
+        let mut debugger = Debugger::new("dreamseeker".to_owned());
+        debugger.source_breakpoints.insert(path.clone(), vec![(no_op_source_breakpoint(5), false)]);
+
+        // The file only has one line yet, so line 5 stays unverified.
+        debugger.reapply_breakpoints();
+        assert!(!debugger.source_breakpoints[&path][0].1);
+
+        // Grow the file so line 5 now exists; reapplying should flip it.
+        std::fs::write(&path, "1\n2\n3\n4\n5\n").expect("failed to rewrite temp file");
+        debugger.reapply_breakpoints();
+        assert!(debugger.source_breakpoints[&path][0].1);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn variable_handle_lifecycle() {
+        let mut debugger = Debugger::new("dreamseeker".to_owned());
+
+        let a = debugger.alloc_variable_handle("obj-a".to_owned());
+        let b = debugger.alloc_variable_handle("obj-b".to_owned());
+        assert_ne!(a, b);
+        assert_eq!(debugger.variable_handles.get(&a), Some(&"obj-a".to_owned()));
+
+        debugger.clear_variable_handles();
+        assert!(debugger.variable_handles.is_empty());
+
+        // sync_variable_handles only clears once something has actually
+        // marked the table stale (e.g. the notification listener on a stop).
+        let c = debugger.alloc_variable_handle("obj-c".to_owned());
+        debugger.sync_variable_handles();
+        assert!(debugger.variable_handles.contains_key(&c));
+
+        debugger.handles_stale.store(true, Ordering::SeqCst);
+        debugger.sync_variable_handles();
+        assert!(debugger.variable_handles.is_empty());
+    }
 }
\ No newline at end of file